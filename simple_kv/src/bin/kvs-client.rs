@@ -1,4 +1,7 @@
-use simple_kv::{KvClient, Result};
+use simple_kv::{
+    GetResponse, KvClient, KvsError, RemoveResponse, Request, Response, ScanResponse, Result,
+    SetResponse,
+};
 use std::{net::SocketAddr, process::exit};
 use structopt::StructOpt;
 
@@ -24,6 +27,22 @@ enum Opt {
         #[structopt(long, default_value = "127.0.0.1:4000", parse(try_from_str))]
         addr: SocketAddr,
     },
+    Scan {
+        #[structopt(name = "START", required = true)]
+        start: String,
+        #[structopt(name = "END", required = true)]
+        end: String,
+        #[structopt(long, default_value = "127.0.0.1:4000", parse(try_from_str))]
+        addr: SocketAddr,
+    },
+    /// Submit several operations in one round trip, e.g.
+    /// `batch set a 1 set b 2 get a rm b`.
+    Batch {
+        #[structopt(name = "OP", required = true)]
+        ops: Vec<String>,
+        #[structopt(long, default_value = "127.0.0.1:4000", parse(try_from_str))]
+        addr: SocketAddr,
+    },
 }
 
 fn main() {
@@ -52,6 +71,74 @@ fn run(opt: Opt) -> Result<()> {
             let mut client = KvClient::connect(addr)?;
             client.remove(key)?;
         }
+        Opt::Scan { start, end, addr } => {
+            let mut client = KvClient::connect(addr)?;
+            for (key, value) in client.scan(start, end)? {
+                println!("{} {}", key, value);
+            }
+        }
+        Opt::Batch { ops, addr } => {
+            let requests = parse_batch(&ops)?;
+            let mut client = KvClient::connect(addr)?;
+            for resp in client.batch(requests)? {
+                print_response(resp);
+            }
+        }
     }
     Ok(())
 }
+
+// Turn a flat token list like `set a 1 get a rm b` into a list of requests,
+// each verb consuming its own arguments.
+fn parse_batch(ops: &[String]) -> Result<Vec<Request>> {
+    let mut requests = Vec::new();
+    let mut iter = ops.iter();
+    while let Some(verb) = iter.next() {
+        let req = match verb.as_str() {
+            "set" => {
+                let key = next_arg(&mut iter)?;
+                let value = next_arg(&mut iter)?;
+                Request::Set { key, value }
+            }
+            "get" => Request::Get {
+                key: next_arg(&mut iter)?,
+            },
+            "rm" => Request::Remove {
+                key: next_arg(&mut iter)?,
+            },
+            other => {
+                return Err(KvsError::StringErr(format!(
+                    "Unknown batch operation: {}",
+                    other
+                )))
+            }
+        };
+        requests.push(req);
+    }
+    Ok(requests)
+}
+
+fn next_arg(iter: &mut std::slice::Iter<String>) -> Result<String> {
+    iter.next()
+        .cloned()
+        .ok_or_else(|| KvsError::StringErr("Missing argument in batch operation".to_owned()))
+}
+
+// Render one sub-response the way the matching single-operation subcommand does.
+fn print_response(resp: Response) {
+    match resp {
+        Response::Get(GetResponse::Ok(Some(value))) => println!("{}", value),
+        Response::Get(GetResponse::Ok(None)) => println!("Key not found"),
+        Response::Set(SetResponse::Ok(())) | Response::Remove(RemoveResponse::Ok(())) => {}
+        Response::Scan(ScanResponse::Ok(pairs)) => {
+            for (key, value) in pairs {
+                println!("{} {}", key, value);
+            }
+        }
+        Response::Batch(responses) => responses.into_iter().for_each(print_response),
+        Response::Get(GetResponse::Err(msg))
+        | Response::Set(SetResponse::Err(msg))
+        | Response::Remove(RemoveResponse::Err(msg))
+        | Response::Scan(ScanResponse::Err(msg)) => eprintln!("{}", msg),
+    }
+}
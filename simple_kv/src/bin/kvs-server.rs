@@ -1,11 +1,13 @@
 #[macro_use] extern crate log;
 
 use simple_kv::{KvsError, KvsEngine, Result, KvServer, KvStore, SledStore};
+use simple_kv::thread_pool::{NaiveThreadPool, RayonThreadPool, SharedQueueThreadPool, ThreadPool};
 use std::{
     env::current_dir,
     net::SocketAddr,
     str::FromStr,
     process::exit,
+    thread,
     fs,
 };
 use structopt::StructOpt;
@@ -38,6 +40,46 @@ impl FromStr for Engine {
     }
 }
 
+#[allow(non_camel_case_types)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+enum Pool {
+    shared,
+    rayon,
+    naive,
+}
+
+impl FromStr for Pool {
+    type Err = KvsError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "shared" => Ok(Pool::shared),
+            "rayon" => Ok(Pool::rayon),
+            "naive" => Ok(Pool::naive),
+            _ => Err(KvsError::StringErr("Invalid thread pool name".to_owned())),
+        }
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+enum Mode {
+    blocking,
+    evented,
+}
+
+impl FromStr for Mode {
+    type Err = KvsError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "blocking" => Ok(Mode::blocking),
+            "evented" => Ok(Mode::evented),
+            _ => Err(KvsError::StringErr("Invalid server mode".to_owned())),
+        }
+    }
+}
+
 #[derive(Debug, StructOpt)]
 struct Opt {
     #[structopt(long,
@@ -48,6 +90,16 @@ struct Opt {
     #[structopt(long,
     parse(try_from_str))]
     engine: Option<Engine>,
+    #[structopt(long,
+    required = false,
+    default_value = "shared",
+    parse(try_from_str))]
+    pool: Pool,
+    #[structopt(long,
+    required = false,
+    default_value = "blocking",
+    parse(try_from_str))]
+    mode: Mode,
 }
 
 fn get_engine_name_from_file() -> Result<Option<Engine>> {
@@ -67,9 +119,32 @@ fn get_engine_name_from_file() -> Result<Option<Engine>> {
     }
 }
 
-fn run_kv_server<E: KvsEngine>(engine: E, addr: SocketAddr) -> Result<()> {
-    let mut server = KvServer::new(engine, addr);
-    Ok(server.run()?)
+fn run_kv_server<E: KvsEngine, P: ThreadPool>(
+    engine: E,
+    addr: SocketAddr,
+    pool: P,
+    mode: Mode,
+) -> Result<()> {
+    let mut server = KvServer::new(engine, addr, pool);
+    match mode {
+        Mode::blocking => Ok(server.run()?),
+        Mode::evented => Ok(server.run_evented()?),
+    }
+}
+
+// Build the chosen thread pool with one worker per available CPU (the `naive`
+// pool ignores the count and spawns a thread per job) and hand the
+// engine over to the server. The pool type is selected at runtime by `--pool`,
+// so dispatch here rather than making `main` generic.
+fn start<E: KvsEngine>(engine: E, addr: SocketAddr, pool: Pool, mode: Mode) -> Result<()> {
+    let threads = thread::available_parallelism()
+        .map(|n| n.get() as u32)
+        .unwrap_or(4);
+    match pool {
+        Pool::shared => run_kv_server(engine, addr, SharedQueueThreadPool::new(threads)?, mode),
+        Pool::rayon => run_kv_server(engine, addr, RayonThreadPool::new(threads)?, mode),
+        Pool::naive => run_kv_server(engine, addr, NaiveThreadPool::new(threads)?, mode),
+    }
 }
 
 fn main() -> Result<()> {
@@ -94,6 +169,8 @@ fn main() -> Result<()> {
 
     info!("kvs-server {}", env!("CARGO_PKG_VERSION"));
     info!("Storage engine: {:?}", opt.engine);
+    info!("Thread pool: {:?}", opt.pool);
+    info!("Server mode: {:?}", opt.mode);
     info!("Listening on {}", opt.addr);
 
     let engine = opt.engine.unwrap();
@@ -102,11 +179,11 @@ fn main() -> Result<()> {
     match engine {
         Engine::kvs => {
             let engine = KvStore::open(&current_dir()?)?;
-            run_kv_server(engine, opt.addr)?;
+            start(engine, opt.addr, opt.pool, opt.mode)?;
         },
         Engine::sled => {
             let engine = SledStore::new(sled::open(current_dir()?)?);
-            run_kv_server(engine, opt.addr)?;
+            start(engine, opt.addr, opt.pool, opt.mode)?;
         },
     }
 
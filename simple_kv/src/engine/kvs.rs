@@ -1,6 +1,7 @@
 use super::KvsEngine;
 use crate::{KvsError, Result};
 use crossbeam_skiplist::SkipMap;
+use memmap::Mmap;
 use serde::{Deserialize, Serialize};
 use serde_json::{self, Deserializer};
 use std::{
@@ -8,8 +9,9 @@ use std::{
     collections::BTreeMap,
     fs::{self, File, OpenOptions},
     io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    ops::RangeBounds,
     path::{self, Path, PathBuf},
-    sync::atomic::{AtomicUsize, Ordering},
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
     sync::{
         mpsc::{self, Receiver, Sender},
         Arc, Mutex,
@@ -19,6 +21,11 @@ use std::{
 
 const COMPACTION: u64 = 1024 * 1024;
 
+/// Format byte written at the head of every hint file. A hint whose first
+/// byte does not match (or which is truncated) is ignored and the matching
+/// log generation is replayed instead.
+const HINT_VERSION: u8 = 1;
+
 /// `BufWriterWithPos` is a wrapper of `BufWriter` to simplify positioning.
 struct BufWriterWithPos<W: Write + Seek> {
     writer: BufWriter<W>,
@@ -100,8 +107,21 @@ struct CmdPos {
 struct KvStoreReader {
     path: Arc<PathBuf>,
     safe_point: Arc<AtomicUsize>,
+    // The current writer generation, shared with `KvStoreWriter`. Anything
+    // below this may be memory-mapped; the live writer gen must not be, since
+    // the writer concurrently appends to it. (A gen below `cur_gen` that is
+    // still being rewritten by compaction may grow, but is only ever remapped
+    // on demand, never mapped while the foreground writer appends.)
+    cur_gen: Arc<AtomicU64>,
     // for single thread
-    readers: RefCell<BTreeMap<u64, BufReaderWithPos<File>>>,
+    //
+    // Every generation below `cur_gen` is memory-mapped read-only, so reads are
+    // plain slices into the OS page cache and carry no per-`get` syscall. The
+    // maps are shared through the page cache across every cloned reader. Note a
+    // generation currently being rewritten by compaction (`cur_gen - 1`) is
+    // still below `cur_gen` and so lives here: it grows as the background thread
+    // appends, and is remapped on demand (see `read_command`).
+    readers: RefCell<BTreeMap<u64, Mmap>>,
 }
 
 impl KvStoreReader {
@@ -119,15 +139,37 @@ impl KvStoreReader {
 
     fn read_command(&self, cmd_pos: CmdPos) -> Result<Cmd> {
         self.close_stale_handle();
+        // The live writer generation is never memory-mapped: it is read through
+        // a buffered file handle instead. Every generation below it is mapped,
+        // including the one compaction may still be appending to (see below).
+        if cmd_pos.gen >= self.cur_gen.load(Ordering::SeqCst) {
+            let mut reader = BufReaderWithPos::new(File::open(log_path(&self.path, cmd_pos.gen))?)?;
+            reader.seek(SeekFrom::Start(cmd_pos.pos))?;
+            let cmd_reader = reader.take(cmd_pos.len);
+            return Ok(serde_json::from_reader(cmd_reader)?);
+        }
+
         let mut readers = self.readers.borrow_mut();
-        if !readers.contains_key(&cmd_pos.gen) {
-            let new_reader = BufReaderWithPos::new(File::open(log_path(&self.path, cmd_pos.gen))?)?;
-            readers.insert(cmd_pos.gen, new_reader);
+        let end = (cmd_pos.pos + cmd_pos.len) as usize;
+        // Map the generation lazily on first access, and remap it when the
+        // cached mapping no longer covers the wanted command. The real
+        // invariant is only that the *live writer* gen (handled above) is never
+        // mapped. The gen under active compaction (`cur_gen - 1`) is mapped here
+        // yet still grows as `spawn_background` appends to it; because
+        // compaction flushes before publishing each entry's `CmdPos`, a mapping
+        // that is too short for the wanted command just means the gen grew since
+        // we mapped it, so the `mmap.len() < end` branch remaps it on demand.
+        let need_remap = match readers.get(&cmd_pos.gen) {
+            Some(mmap) => mmap.len() < end,
+            None => true,
+        };
+        if need_remap {
+            let file = File::open(log_path(&self.path, cmd_pos.gen))?;
+            let mmap = unsafe { Mmap::map(&file)? };
+            readers.insert(cmd_pos.gen, mmap);
         }
-        let reader = readers.get_mut(&cmd_pos.gen).unwrap();
-        reader.seek(SeekFrom::Start(cmd_pos.pos))?;
-        let reader = reader.take(cmd_pos.len);
-        Ok(serde_json::from_reader(reader)?)
+        let mmap = readers.get(&cmd_pos.gen).unwrap();
+        Ok(serde_json::from_slice(&mmap[cmd_pos.pos as usize..end])?)
     }
 }
 
@@ -136,6 +178,7 @@ impl Clone for KvStoreReader {
         KvStoreReader {
             path: Arc::clone(&self.path),
             safe_point: Arc::clone(&self.safe_point),
+            cur_gen: Arc::clone(&self.cur_gen),
             readers: RefCell::new(BTreeMap::new()),
         }
     }
@@ -146,6 +189,10 @@ struct KvStoreWriter {
     // reader: KvStoreReader,
     writer: BufWriterWithPos<File>,
     cur_gen: u64,
+    // Shared with every `KvStoreReader` so they can tell the live writer gen
+    // (which must not be mmapped) from closed ones. Kept in step with `cur_gen`
+    // whenever the writer rotates to a new log file.
+    cur_gen_shared: Arc<AtomicU64>,
     compaction_size: u64,
     path: Arc<PathBuf>,
     key_gen_map: Arc<SkipMap<String, CmdPos>>,
@@ -181,6 +228,9 @@ impl KvStoreWriter {
             // `cur_gen` and `writer` should be exclusive by current write thread.
             let compaction_gen = self.cur_gen + 1;
             self.cur_gen += 2;
+            // Publish the new live gen before readers can observe `CmdPos`es
+            // pointing into it, so they never try to mmap a gen we append to.
+            self.cur_gen_shared.store(self.cur_gen, Ordering::SeqCst);
             self.writer = new_log_file(&self.path, self.cur_gen)?;
             // Immediately reset the `compaction_size`, so that writer thread can
             // prepare for next compaction generation while compaction is running.
@@ -207,6 +257,9 @@ impl KvStoreWriter {
                 // `cur_gen` and `writer` should be exclusive by current write thread.
                 let compaction_gen = self.cur_gen + 1;
                 self.cur_gen += 2;
+                // Publish the new live gen before readers can observe `CmdPos`es
+                // pointing into it, so they never try to mmap a gen we append to.
+                self.cur_gen_shared.store(self.cur_gen, Ordering::SeqCst);
                 self.writer = new_log_file(&self.path, self.cur_gen)?;
                 // Immediately reset the `compaction_size`, so that writer thread can
                 // prepare for next compaction generation while compaction is running.
@@ -236,6 +289,10 @@ fn spawn_background(
             let mut compaction_writer = new_log_file(&*path, compaction_gen)?;
 
             let mut new_pos = 0;
+            // Remember every `(key, pos, len)` we emit so we can dump a hint
+            // file once the generation is fully written; on the next `open`
+            // this lets us rebuild the index without replaying the log.
+            let mut hint_entries = Vec::new();
             for entry in key_gen_map.iter() {
                 let cmd_pos = entry.value().clone();
                 let cmd = reader.read_command(cmd_pos.clone())?;
@@ -253,9 +310,23 @@ fn spawn_background(
                         len: cmd_pos.len,
                     },
                 );
+                hint_entries.push(HintEntry {
+                    key: entry.key().clone(),
+                    pos: new_pos,
+                    len: cmd_pos.len,
+                });
                 new_pos += cmd_pos.len;
             }
 
+            // Best-effort: a missing hint just means the generation is replayed
+            // from its log next time, so a write failure is logged, not fatal.
+            if let Err(e) = write_hint_file(&*path, compaction_gen, &hint_entries) {
+                error!(
+                    "[compaction_background] Cannot write hint for gen {}: {}",
+                    compaction_gen, e
+                );
+            }
+
             reader
                 .safe_point
                 .store(compaction_gen as usize, Ordering::SeqCst);
@@ -273,6 +344,16 @@ fn spawn_background(
                         path, e
                     );
                 }
+                // Drop the matching hint too so it can't outlive its log.
+                let hint = hint_path(&path, gen);
+                if hint.is_file() {
+                    if let Err(e) = fs::remove_file(&hint) {
+                        error!(
+                            "[compaction_background] Hint {:?} cannot be remove now: {}",
+                            hint, e
+                        );
+                    }
+                }
             }
         }
         Ok(())
@@ -321,6 +402,21 @@ impl KvsEngine for KvStore {
     fn remove(&self, key: String) -> Result<()> {
         self.writer.lock().unwrap().remove(key)
     }
+
+    /// Walk the ordered `key_gen_map` over `range` and resolve each command
+    /// through the reader, reusing the same lock-free read path as `get`.
+    fn scan(&self, range: impl RangeBounds<String>) -> Result<Vec<(String, String)>> {
+        let mut pairs = Vec::new();
+        for entry in self.key_gen_map.range(range) {
+            let cmd_pos = entry.value().clone();
+            if let Cmd::Set { value, .. } = self.reader.read_command(cmd_pos)? {
+                pairs.push((entry.key().clone(), value));
+            } else {
+                return Err(KvsError::UndefCmdline);
+            }
+        }
+        Ok(pairs)
+    }
 }
 
 impl KvStore {
@@ -329,24 +425,31 @@ impl KvStore {
         let path = Arc::new(path.to_path_buf());
         fs::create_dir_all(&*path)?;
 
-        let mut readers = BTreeMap::new();
         let key_gen_map = SkipMap::new();
         let mut compaction_size = 0 as u64;
         let gen_list = get_sorted_gen_list(&path)?;
         for &gen in &gen_list {
-            let mut reader = BufReaderWithPos::new(File::open(log_path(&path, gen))?)?;
-            compaction_size += load(gen, &mut reader, &key_gen_map)?;
-            readers.insert(gen, reader);
+            // Prefer the hint file: it lets us rebuild this generation's index
+            // entries directly. Fall back to a full log replay when the hint is
+            // absent, from an older format, or truncated (e.g. the live gen).
+            if let Some(size) = load_hint(gen, &path, &key_gen_map)? {
+                compaction_size += size;
+            } else {
+                let mut reader = BufReaderWithPos::new(File::open(log_path(&path, gen))?)?;
+                compaction_size += load(gen, &mut reader, &key_gen_map)?;
+            }
         }
 
         let cur_gen = *(gen_list.last().unwrap_or(&0)) + 1;
         let writer = new_log_file(&path, cur_gen)?;
         let safe_point = Arc::new(AtomicUsize::new(0));
+        let cur_gen_shared = Arc::new(AtomicU64::new(cur_gen));
 
         let reader = KvStoreReader {
             path: Arc::clone(&path),
             safe_point,
-            readers: RefCell::new(readers),
+            cur_gen: Arc::clone(&cur_gen_shared),
+            readers: RefCell::new(BTreeMap::new()),
         };
 
         let reader_clone = reader.clone();
@@ -356,6 +459,7 @@ impl KvStore {
         let writer = KvStoreWriter {
             writer,
             cur_gen,
+            cur_gen_shared,
             compaction_size,
             path: Arc::clone(&path),
             key_gen_map: Arc::clone(&key_gen_map),
@@ -380,6 +484,83 @@ fn log_path(path: &Path, gen: u64) -> PathBuf {
     buf
 }
 
+fn hint_path(path: &Path, gen: u64) -> PathBuf {
+    let mut buf = path.to_path_buf();
+    buf.push(Path::new(&format!("{}.hint", gen)));
+    buf
+}
+
+/// Write the hint (index) file for a freshly compacted generation.
+///
+/// The file starts with a single [`HINT_VERSION`] byte followed by the
+/// `(key, pos, len)` entries serialized as a JSON stream, mirroring how the
+/// log itself is laid out.
+fn write_hint_file(path: &Path, gen: u64, entries: &[HintEntry]) -> Result<()> {
+    let file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(hint_path(path, gen))?;
+    let mut writer = BufWriter::new(file);
+    writer.write_all(&[HINT_VERSION])?;
+    for entry in entries {
+        serde_json::to_writer(&mut writer, entry)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Rebuild a generation's index entries from its hint file.
+///
+/// Returns `Ok(Some(size))` with the overwritten-byte total to fold into the
+/// compaction counter when the hint is present and well-formed, or `Ok(None)`
+/// when there is no usable hint so the caller should replay the log. A hint
+/// with a mismatched version byte or a truncated entry stream is treated as
+/// absent and leaves `key_gen_map` untouched.
+fn load_hint(
+    gen: u64,
+    path: &Path,
+    key_gen_map: &SkipMap<String, CmdPos>,
+) -> Result<Option<u64>> {
+    let hint = hint_path(path, gen);
+    if !hint.is_file() {
+        return Ok(None);
+    }
+
+    let mut file = File::open(&hint)?;
+    let mut version = [0u8; 1];
+    if file.read_exact(&mut version).is_err() || version[0] != HINT_VERSION {
+        return Ok(None);
+    }
+
+    // Parse the whole stream first; only apply the entries once we know the
+    // hint is intact, so a truncated file never leaves a partial index behind.
+    let mut entries = Vec::new();
+    let stream = Deserializer::from_reader(BufReader::new(file)).into_iter::<HintEntry>();
+    for entry in stream {
+        match entry {
+            Ok(entry) => entries.push(entry),
+            Err(_) => return Ok(None),
+        }
+    }
+
+    let mut compaction_size = 0 as u64;
+    for entry in entries {
+        if let Some(old_cmd) = key_gen_map.get(&entry.key) {
+            compaction_size += old_cmd.value().len;
+        }
+        key_gen_map.insert(
+            entry.key,
+            CmdPos {
+                gen,
+                pos: entry.pos,
+                len: entry.len,
+            },
+        );
+    }
+    Ok(Some(compaction_size))
+}
+
 fn new_log_file(path: &Path, gen: u64) -> Result<BufWriterWithPos<File>> {
     let new_log_path = log_path(path, gen);
     let writer = OpenOptions::new()
@@ -446,6 +627,18 @@ fn load(
     Ok(compaction_size)
 }
 
+/// `HintEntry` is one `(key, pos, len)` record inside a hint file, describing
+/// where a key's command lives in the matching compacted log generation.
+#[derive(Serialize, Deserialize, Debug)]
+struct HintEntry {
+    /// The key this entry indexes.
+    key: String,
+    /// Offset of the command within the generation's log file.
+    pos: u64,
+    /// Length in bytes of the command.
+    len: u64,
+}
+
 /// `Cmd` is Serializable & Deserializable
 #[derive(Serialize, Deserialize, Debug)]
 enum Cmd {
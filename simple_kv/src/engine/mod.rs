@@ -1,4 +1,5 @@
 use crate::{ Result };
+use std::ops::RangeBounds;
 
 mod kvs;
 mod sleds;
@@ -14,4 +15,7 @@ pub trait KvsEngine: Clone + Send + 'static {
     fn get(&self, key: String) -> Result<Option<String>>;
     /// Remove a given string key.
     fn remove(&self, key: String) -> Result<()>;
+    /// Collect every `(key, value)` pair whose key falls within `range`,
+    /// in ascending key order.
+    fn scan(&self, range: impl RangeBounds<String>) -> Result<Vec<(String, String)>>;
 }
\ No newline at end of file
@@ -1,5 +1,6 @@
 use crate::{KvsEngine, KvsError, Result};
 use sled::Db;
+use std::ops::{Bound, RangeBounds};
 
 /// `SledStore` is a top level wrapper of various implementation of `KvsEngine`.
 #[derive(Clone)]
@@ -37,4 +38,32 @@ impl KvsEngine for SledStore {
         tree.flush()?;
         Ok(())
     }
+
+    fn scan(&self, range: impl RangeBounds<String>) -> Result<Vec<(String, String)>> {
+        // `sled` ranges over byte slices, so translate the `String` bounds.
+        let start = map_bound(range.start_bound());
+        let end = map_bound(range.end_bound());
+        let bounds = (
+            start.as_ref().map(Vec::as_slice),
+            end.as_ref().map(Vec::as_slice),
+        );
+        let mut pairs = Vec::new();
+        for res in self.db.range::<&[u8], _>(bounds) {
+            let (key, value) = res?;
+            pairs.push((
+                String::from_utf8(key.as_ref().to_vec())?,
+                String::from_utf8(value.as_ref().to_vec())?,
+            ));
+        }
+        Ok(pairs)
+    }
+}
+
+// Translate a `String` bound into the owned byte bound `sled::range` expects.
+fn map_bound(bound: Bound<&String>) -> Bound<Vec<u8>> {
+    match bound {
+        Bound::Included(s) => Bound::Included(s.clone().into_bytes()),
+        Bound::Excluded(s) => Bound::Excluded(s.clone().into_bytes()),
+        Bound::Unbounded => Bound::Unbounded,
+    }
 }
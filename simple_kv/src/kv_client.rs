@@ -1,5 +1,5 @@
 use crate::{
-    protocol::{GetResponse, RemoveResponse, Request, Response, SetResponse},
+    protocol::{GetResponse, RemoveResponse, Request, Response, ScanResponse, SetResponse},
     KvsError, Result,
 };
 use serde::Deserialize;
@@ -63,6 +63,44 @@ impl KvClient {
         }
     }
 
+    /// Scan every key in `[start, end)` from the server, in ascending order.
+    pub fn scan(&mut self, start: String, end: String) -> Result<Vec<(String, String)>> {
+        let request = Request::Scan { start, end };
+        serde_json::to_writer(&mut self.writer, &request)?;
+        self.writer.flush()?;
+        let resp = Response::deserialize(&mut self.reader)?;
+        debug!("[client scan] Get response from server {:?}", &resp);
+        match resp {
+            Response::Scan(scan) => match scan {
+                ScanResponse::Ok(pairs) => Ok(pairs),
+                ScanResponse::Err(msg) => Err(KvsError::StringErr(msg)),
+            },
+            _ => {
+                panic!("[client scan] Reach unreachable code");
+            }
+        }
+    }
+
+    /// Submit several requests in a single round trip.
+    ///
+    /// The server applies the sub-operations sequentially in the order given
+    /// and returns one [`Response`] per request in the same order. The batch
+    /// is not atomic across the store — other writers may interleave — it only
+    /// amortizes the TCP round trip of issuing the operations one by one.
+    pub fn batch(&mut self, requests: Vec<Request>) -> Result<Vec<Response>> {
+        let request = Request::Batch(requests);
+        serde_json::to_writer(&mut self.writer, &request)?;
+        self.writer.flush()?;
+        let resp = Response::deserialize(&mut self.reader)?;
+        debug!("[client batch] Get response from server {:?}", &resp);
+        match resp {
+            Response::Batch(responses) => Ok(responses),
+            _ => {
+                panic!("[client batch] Reach unreachable code");
+            }
+        }
+    }
+
     /// Remove a string key in the server.
     pub fn remove(&mut self, key: String) -> Result<()> {
         let request = Request::Remove { key };
@@ -1,15 +1,25 @@
 use crate::thread_pool::ThreadPool;
 use crate::{
-    protocol::{GetResponse, RemoveResponse, Request, Response, SetResponse},
+    protocol::{GetResponse, RemoveResponse, Request, Response, ScanResponse, SetResponse},
     KvsEngine, Result,
 };
+use crossbeam::channel::{self, Receiver, Sender};
+use mio::net::{TcpListener as MioListener, TcpStream as MioStream};
+use mio::{Events, Interest, Poll, Token, Waker};
 use serde_json::Deserializer;
-use std::io::Write;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Read, Write};
+use std::sync::Arc;
 use std::{
     io::{BufReader, BufWriter},
     net::{SocketAddr, TcpListener, TcpStream},
 };
 
+// Reserved tokens for the event loop; connection tokens start after these.
+const LISTENER: Token = Token(0);
+const WAKER: Token = Token(1);
+const FIRST_CONN: usize = 2;
+
 /// `KvServer` is a top level wrapper of various implementation of `KvsEngine`.
 pub struct KvServer<E: KvsEngine, P: ThreadPool> {
     engine: E,
@@ -44,6 +54,337 @@ impl<E: KvsEngine, P: ThreadPool> KvServer<E, P> {
         }
         Ok(())
     }
+
+    /// run a `KvServer` in non-blocking event-loop mode.
+    ///
+    /// A single `mio::Poll` watches the listener and every accepted stream via
+    /// their `AsRawFd`, so a mostly-idle keep-alive connection costs a poll
+    /// slot rather than a whole pool thread. Bytes are framed incrementally by
+    /// [`RequestDecoder`]; only a fully-decoded `Request` is handed to the
+    /// `ThreadPool`. Workers encode their reply and push it back over a channel,
+    /// then wake the poll so the loop flushes it on the owning connection.
+    pub fn run_evented(&mut self) -> Result<()> {
+        let mut poll = Poll::new()?;
+        let mut events = Events::with_capacity(1024);
+        let mut listener = MioListener::bind(self.addr)?;
+        poll.registry()
+            .register(&mut listener, LISTENER, Interest::READABLE)?;
+
+        // Workers send `(token, seq, response_bytes)` back here; the waker nudges
+        // the loop out of `poll` so it can flush them. `seq` is the request's
+        // per-connection sequence number so replies are re-ordered to match the
+        // order the requests arrived, even when pipelined jobs finish out of order.
+        type Reply = (Token, u64, Vec<u8>);
+        let (tx, rx): (Sender<Reply>, Receiver<Reply>) = channel::unbounded();
+        let waker = Arc::new(Waker::new(poll.registry(), WAKER)?);
+
+        let mut conns: HashMap<usize, Connection> = HashMap::new();
+        let mut next_token = FIRST_CONN;
+
+        loop {
+            poll.poll(&mut events, None)?;
+            for event in events.iter() {
+                match event.token() {
+                    LISTENER => loop {
+                        match listener.accept() {
+                            Ok((mut stream, addr)) => {
+                                let token = Token(next_token);
+                                next_token += 1;
+                                poll.registry().register(
+                                    &mut stream,
+                                    token,
+                                    Interest::READABLE,
+                                )?;
+                                debug!("[kv_server] Accepted {} as {:?}", addr, token);
+                                conns.insert(token.0, Connection::new(stream));
+                            }
+                            Err(ref e) if would_block(e) => break,
+                            Err(e) => {
+                                error!("[kv_server] Accept failed: {}", e);
+                                break;
+                            }
+                        }
+                    },
+                    WAKER => {
+                        // Drain every completed reply, slot it into its owner's
+                        // reorder buffer, and flush as many in-order replies as
+                        // are now contiguous.
+                        while let Ok((token, seq, bytes)) = rx.try_recv() {
+                            let mut drop_conn = false;
+                            if let Some(conn) = conns.get_mut(&token.0) {
+                                if conn.deliver(seq, bytes) {
+                                    poll.registry().reregister(
+                                        &mut conn.stream,
+                                        token,
+                                        Interest::READABLE | Interest::WRITABLE,
+                                    )?;
+                                }
+                                // A half-closed connection with no bytes left to
+                                // write and every reply delivered is done.
+                                drop_conn = conn.finished();
+                            }
+                            if drop_conn {
+                                if let Some(mut conn) = conns.remove(&token.0) {
+                                    let _ = poll.registry().deregister(&mut conn.stream);
+                                }
+                            }
+                        }
+                    }
+                    token => {
+                        let mut close = false;
+                        if event.is_readable() {
+                            if let Some(conn) = conns.get_mut(&token.0) {
+                                match conn.read_requests() {
+                                    Ok((reqs, eof)) => {
+                                        for req in reqs {
+                                            // Stamp each request with its arrival
+                                            // order so replies can be reordered.
+                                            let seq = conn.next_send_seq();
+                                            let engine = self.engine.clone();
+                                            let tx = tx.clone();
+                                            let waker = Arc::clone(&waker);
+                                            self.pool.spawn(move || {
+                                                let resp = handle_request(&engine, req);
+                                                match serde_json::to_vec(&resp) {
+                                                    Ok(bytes) => {
+                                                        let _ = tx.send((token, seq, bytes));
+                                                        let _ = waker.wake();
+                                                    }
+                                                    Err(e) => error!(
+                                                        "[kv_server] Encode response failed: {}",
+                                                        e
+                                                    ),
+                                                }
+                                            });
+                                        }
+                                        // Peer half-closed (the write-all →
+                                        // shutdown(Write) → read-all pipelining
+                                        // idiom): mark the connection draining but
+                                        // keep it alive until every in-flight reply
+                                        // has been delivered and flushed, then tear
+                                        // it down. Closing now would discard the
+                                        // replies the client is still waiting to read.
+                                        if eof {
+                                            conn.draining = true;
+                                            close = conn.finished();
+                                        }
+                                    }
+                                    Err(e) => {
+                                        error!("[kv_server] Read/decode failed: {}", e);
+                                        close = true;
+                                    }
+                                }
+                            }
+                        }
+                        if !close && event.is_writable() {
+                            if let Some(conn) = conns.get_mut(&token.0) {
+                                match conn.flush_writes() {
+                                    Ok(done) if done => {
+                                        // Fully flushed: a draining connection with
+                                        // all replies delivered can now be closed;
+                                        // otherwise go back to waiting for reads.
+                                        if conn.finished() {
+                                            close = true;
+                                        } else {
+                                            poll.registry().reregister(
+                                                &mut conn.stream,
+                                                token,
+                                                Interest::READABLE,
+                                            )?;
+                                        }
+                                    }
+                                    Ok(_) => {}
+                                    Err(e) => {
+                                        error!("[kv_server] Write failed: {}", e);
+                                        close = true;
+                                    }
+                                }
+                            }
+                        }
+                        if close {
+                            if let Some(mut conn) = conns.remove(&token.0) {
+                                let _ = poll.registry().deregister(&mut conn.stream);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// `Connection` holds the per-socket state the event loop needs: the stream
+/// itself, a read buffer that accumulates partial requests, and an outbound
+/// buffer of encoded responses waiting to be written.
+struct Connection {
+    stream: MioStream,
+    decoder: RequestDecoder,
+    write_buf: Vec<u8>,
+    written: usize,
+    // Sequence counters that keep pipelined replies in request order: each
+    // incoming request is stamped with `send_seq`, and replies are only moved
+    // into `write_buf` once every earlier reply (up to `recv_seq`) has arrived.
+    send_seq: u64,
+    recv_seq: u64,
+    pending: BTreeMap<u64, Vec<u8>>,
+    // Set once the peer half-closes its write half. The connection is torn down
+    // only after `finished()`, so in-flight replies are never discarded.
+    draining: bool,
+}
+
+impl Connection {
+    fn new(stream: MioStream) -> Self {
+        Connection {
+            stream,
+            decoder: RequestDecoder::new(),
+            write_buf: Vec::new(),
+            written: 0,
+            send_seq: 0,
+            recv_seq: 0,
+            pending: BTreeMap::new(),
+            draining: false,
+        }
+    }
+
+    // A draining connection is finished once every request has produced a reply
+    // (`send_seq == recv_seq`) and the write buffer is fully flushed.
+    fn finished(&self) -> bool {
+        self.draining && self.send_seq == self.recv_seq && self.write_buf.is_empty()
+    }
+
+    // Drain everything currently readable and hand back the fully-framed
+    // requests along with whether the peer half-closed (`read` returned 0). A
+    // `WouldBlock` just means we have read all that is ready.
+    fn read_requests(&mut self) -> Result<(Vec<Request>, bool)> {
+        let mut chunk = [0u8; 4096];
+        let mut eof = false;
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => {
+                    eof = true;
+                    break;
+                }
+                Ok(n) => self.decoder.extend(&chunk[..n]),
+                Err(ref e) if would_block(e) => break,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok((self.decoder.decode()?, eof))
+    }
+
+    // Allocate the next request's sequence number.
+    fn next_send_seq(&mut self) -> u64 {
+        let seq = self.send_seq;
+        self.send_seq += 1;
+        seq
+    }
+
+    // Accept a completed reply and append every now-contiguous reply to the
+    // write buffer. Returns `true` if there is unwritten data to flush.
+    fn deliver(&mut self, seq: u64, bytes: Vec<u8>) -> bool {
+        self.pending.insert(seq, bytes);
+        while let Some(next) = self.pending.remove(&self.recv_seq) {
+            self.write_buf.extend_from_slice(&next);
+            self.recv_seq += 1;
+        }
+        self.written < self.write_buf.len()
+    }
+
+    // Push out as much of the pending buffer as the socket will take. Returns
+    // `true` once the buffer is fully drained.
+    fn flush_writes(&mut self) -> Result<bool> {
+        while self.written < self.write_buf.len() {
+            match self.stream.write(&self.write_buf[self.written..]) {
+                Ok(0) => break,
+                Ok(n) => self.written += n,
+                Err(ref e) if would_block(e) => return Ok(false),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        if self.written >= self.write_buf.len() {
+            self.write_buf.clear();
+            self.written = 0;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+/// `RequestDecoder` turns an incremental byte stream into whole `Request`s,
+/// replacing the blocking `Deserializer::from_reader(...).into_iter()` path so
+/// a partially-received request never blocks the event loop.
+struct RequestDecoder {
+    buf: Vec<u8>,
+}
+
+impl RequestDecoder {
+    fn new() -> Self {
+        RequestDecoder { buf: Vec::new() }
+    }
+
+    fn extend(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    // Pull every complete request out of the buffer, leaving any trailing
+    // partial request behind for the next read.
+    fn decode(&mut self) -> Result<Vec<Request>> {
+        let mut reqs = Vec::new();
+        let mut consumed = 0;
+        let mut stream = Deserializer::from_slice(&self.buf).into_iter::<Request>();
+        loop {
+            match stream.next() {
+                Some(Ok(req)) => {
+                    consumed = stream.byte_offset();
+                    reqs.push(req);
+                }
+                Some(Err(ref e)) if e.is_eof() => break,
+                Some(Err(e)) => return Err(e.into()),
+                None => break,
+            }
+        }
+        if consumed > 0 {
+            self.buf.drain(..consumed);
+        }
+        Ok(reqs)
+    }
+}
+
+fn would_block(e: &io::Error) -> bool {
+    e.kind() == io::ErrorKind::WouldBlock
+}
+
+// Apply a single decoded request against the engine and build its response.
+// Shared by the blocking `serve` loop and the event-loop workers.
+fn handle_request<E: KvsEngine>(engine: &E, req: Request) -> Response {
+    match req {
+        Request::Get { key } => match engine.get(key) {
+            Ok(opt_string) => Response::Get(GetResponse::Ok(opt_string)),
+            Err(e) => Response::Get(GetResponse::Err(format!("{}", e))),
+        },
+        Request::Set { key, value } => match engine.set(key, value) {
+            Ok(_) => Response::Set(SetResponse::Ok(())),
+            Err(e) => Response::Set(SetResponse::Err(format!("{}", e))),
+        },
+        Request::Remove { key } => match engine.remove(key) {
+            Ok(_) => Response::Remove(RemoveResponse::Ok(())),
+            Err(e) => Response::Remove(RemoveResponse::Err(format!("{}", e))),
+        },
+        Request::Scan { start, end } => match engine.scan(start..end) {
+            Ok(pairs) => Response::Scan(ScanResponse::Ok(pairs)),
+            Err(e) => Response::Scan(ScanResponse::Err(format!("{}", e))),
+        },
+        // Apply each sub-operation in order against the same engine and collect
+        // the replies. Other clients' operations may interleave with them (in
+        // `KvStore` each write takes its `Mutex<KvStoreWriter>` on its own), so
+        // the batch is ordered but not atomic.
+        Request::Batch(reqs) => {
+            Response::Batch(reqs.into_iter().map(|r| handle_request(engine, r)).collect())
+        }
+    }
 }
 
 fn serve<E: KvsEngine>(engine: E, tcp: TcpStream) -> Result<()> {
@@ -54,20 +395,7 @@ fn serve<E: KvsEngine>(engine: E, tcp: TcpStream) -> Result<()> {
     for req in req_stream {
         let req: Request = req?;
         debug!("[server]: Get request from {}: {:?}", &peer_addr, &req);
-        let resp = match req {
-            Request::Get { key } => match engine.get(key) {
-                Ok(opt_string) => Response::Get(GetResponse::Ok(opt_string)),
-                Err(e) => Response::Get(GetResponse::Err(format!("{}", e))),
-            },
-            Request::Set { key, value } => match engine.set(key, value) {
-                Ok(_) => Response::Set(SetResponse::Ok(())),
-                Err(e) => Response::Set(SetResponse::Err(format!("{}", e))),
-            },
-            Request::Remove { key } => match engine.remove(key) {
-                Ok(_) => Response::Remove(RemoveResponse::Ok(())),
-                Err(e) => Response::Remove(RemoveResponse::Err(format!("{}", e))),
-            },
-        };
+        let resp = handle_request(&engine, req);
         serde_json::to_writer(&mut writer, &resp)?;
         writer.flush()?;
         debug!("[server]: Send response to {}: {:?}", &peer_addr, &resp);
@@ -7,6 +7,9 @@ pub use engine::{KvStore, KvsEngine, SledStore};
 pub use kv_client::KvClient;
 pub use kv_server::KvServer;
 pub use kvs_error::{KvsError, Result};
+pub use protocol::{
+    GetResponse, RemoveResponse, Request, Response, ScanResponse, SetResponse,
+};
 
 mod engine;
 mod kv_client;
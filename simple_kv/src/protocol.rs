@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+
+/// `Request` is the wire representation of an operation sent from `KvClient`
+/// to `KvServer`.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Request {
+    /// Get the value of `key`.
+    Get {
+        /// The key to look up.
+        key: String,
+    },
+    /// Set `key` to `value`.
+    Set {
+        /// The key to write.
+        key: String,
+        /// The value to store.
+        value: String,
+    },
+    /// Remove `key`.
+    Remove {
+        /// The key to remove.
+        key: String,
+    },
+    /// Scan every key in `[start, end)` in ascending order.
+    Scan {
+        /// Inclusive lower bound of the range.
+        start: String,
+        /// Exclusive upper bound of the range.
+        end: String,
+    },
+    /// Apply several requests in a single round trip. The sub-operations are
+    /// applied sequentially in the order given; the batch is *not* atomic
+    /// across the store, so other writers may interleave between them.
+    Batch(Vec<Request>),
+}
+
+/// `Response` is the wire representation of a reply sent from `KvServer`
+/// back to `KvClient`.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Response {
+    /// Reply to a [`Request::Get`].
+    Get(GetResponse),
+    /// Reply to a [`Request::Set`].
+    Set(SetResponse),
+    /// Reply to a [`Request::Remove`].
+    Remove(RemoveResponse),
+    /// Reply to a [`Request::Scan`].
+    Scan(ScanResponse),
+    /// Reply to a [`Request::Batch`]: one response per sub-request, in the
+    /// same order the sub-requests were given.
+    Batch(Vec<Response>),
+}
+
+/// Result of a [`Request::Get`].
+#[derive(Debug, Serialize, Deserialize)]
+pub enum GetResponse {
+    /// The value of the key, or `None` when it is absent.
+    Ok(Option<String>),
+    /// The engine failed; carries the error message.
+    Err(String),
+}
+
+/// Result of a [`Request::Set`].
+#[derive(Debug, Serialize, Deserialize)]
+pub enum SetResponse {
+    /// The write succeeded.
+    Ok(()),
+    /// The engine failed; carries the error message.
+    Err(String),
+}
+
+/// Result of a [`Request::Remove`].
+#[derive(Debug, Serialize, Deserialize)]
+pub enum RemoveResponse {
+    /// The remove succeeded.
+    Ok(()),
+    /// The engine failed; carries the error message.
+    Err(String),
+}
+
+/// Result of a [`Request::Scan`].
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ScanResponse {
+    /// The matched `(key, value)` pairs, in ascending key order.
+    Ok(Vec<(String, String)>),
+    /// The engine failed; carries the error message.
+    Err(String),
+}
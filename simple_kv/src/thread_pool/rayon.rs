@@ -1,21 +1,27 @@
 use super::ThreadPool;
 
-use crate::Result;
+use crate::{KvsError, Result};
 
-use std::thread;
+use rayon::{ThreadPool as RayonPool, ThreadPoolBuilder};
 
-/// A naive implement for trait `ThreadPool`.
-pub struct RayonThreadPool;
+/// `RayonThreadPool` wraps a `rayon::ThreadPool` and forwards jobs to it.
+pub struct RayonThreadPool {
+    pool: RayonPool,
+}
 
 impl ThreadPool for RayonThreadPool {
-    fn new(_threads: u32) -> Result<RayonThreadPool> {
-        Ok(RayonThreadPool)
+    fn new(threads: u32) -> Result<RayonThreadPool> {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(threads as usize)
+            .build()
+            .map_err(|e| KvsError::StringErr(format!("{}", e)))?;
+        Ok(RayonThreadPool { pool })
     }
 
     fn spawn<F>(&self, job: F)
     where
         F: FnOnce() + Send + 'static,
     {
-        thread::spawn(job);
+        self.pool.spawn(job);
     }
 }
@@ -2,56 +2,29 @@ use super::ThreadPool;
 
 use crate::Result;
 
-use std::sync::mpsc::{self, Receiver, Sender};
-use std::sync::{Arc, Mutex};
+use crossbeam::channel::{self, Receiver, Sender};
+use std::panic::{self, AssertUnwindSafe};
 use std::thread;
 
 type Job = Box<dyn FnOnce() + Send + 'static>;
 
-/// `SharedQueueThreadPool` is a thread pool based on mpsc::channel.
+/// `SharedQueueThreadPool` is a thread pool backed by a shared `crossbeam` channel.
 ///
-/// `threads` is the number of threads.
-/// `job_sender` sends `Msg` to threads.
-/// `handles` is maintained for exiting.
+/// `new(n)` spawns `n` worker threads that all receive jobs over the same
+/// `job_sender`/`Receiver` pair. Each worker runs its job inside `catch_unwind`
+/// so a panicking job can't take the worker down silently; a `Sentinel` guard
+/// on the worker's stack notices a real unwind and re-spawns a replacement
+/// worker in its `Drop`, so the pool never loses a thread.
 pub struct SharedQueueThreadPool {
     job_sender: Sender<Job>,
 }
 
 impl ThreadPool for SharedQueueThreadPool {
     fn new(threads: u32) -> Result<SharedQueueThreadPool> {
-        let (tx, rx) = mpsc::channel::<Job>();
-        let rx = Arc::new(Mutex::new(rx));
+        let (tx, rx) = channel::unbounded::<Job>();
         for _ in 0..threads {
-            let rx_clone = RxWrapper(Arc::clone(&rx));
-            thread::spawn(move || {
-                loop {
-                    let job = rx_clone.0.lock().unwrap().recv();
-                    match job {
-                        Ok(f) => {
-                            f();
-                            //  I try `catch_unwind` then the compile error occurs
-                            //  ``` rust
-                            //  let res = panic::catch_unwind(f);
-                            //  match res {
-                            //      Ok(_) => {},
-                            //      Err(e) => {
-                            //          error!("[SharedThreadPool]Thread {} panics", id);
-                            //      }
-                            //  }
-                            //  ```
-                            //      the type `dyn std::ops::FnOnce() + std::marker::Send`
-                            //  may not be safely transferred across an unwind boundary
-                            //      So I can't catch the panic and keep the existing thread
-                            //  running.
-                        }
-                        Err(e) => {
-                            debug!("[SharedThreadPool]Maybe Sender was destroyed: {}", e);
-                        }
-                    }
-                }
-            });
+            run_worker(rx.clone());
         }
-
         Ok(SharedQueueThreadPool { job_sender: tx })
     }
 
@@ -63,27 +36,41 @@ impl ThreadPool for SharedQueueThreadPool {
     }
 }
 
-// So I determined to use thread::panicking
-// to let the thread die and spawn another.
-// We can't impl Drop for Arc<Mutex<Receiver<Msg>>>
-// because of the orphan rule.
-struct RxWrapper(Arc<Mutex<Receiver<Job>>>);
+// Spawn a single worker that loops on the shared receiver. The `Sentinel`
+// lives on the worker's stack: if a job lets a panic escape `catch_unwind`,
+// the unwind drops the sentinel while `thread::panicking()` is true, which
+// re-spawns a fresh worker so the pool keeps its thread count.
+fn run_worker(rx: Receiver<Job>) {
+    thread::spawn(move || {
+        let sentinel = Sentinel(rx);
+        loop {
+            match sentinel.0.recv() {
+                Ok(job) => {
+                    // `catch_unwind` swallows the job's panic so the `recv` loop
+                    // keeps serving; the sentinel is only a backstop.
+                    if panic::catch_unwind(AssertUnwindSafe(job)).is_err() {
+                        error!("[SharedThreadPool] A job panicked; worker continues");
+                    }
+                }
+                Err(e) => {
+                    // Sender was dropped: the pool is shutting down. This is not
+                    // a panic, so the sentinel's `Drop` will not re-spawn.
+                    debug!("[SharedThreadPool] Maybe Sender was destroyed: {}", e);
+                    break;
+                }
+            }
+        }
+    });
+}
+
+// `Sentinel` re-spawns a replacement worker when dropped during an unwind.
+// We can't impl `Drop` for the bare `Receiver`, so wrap it here.
+struct Sentinel(Receiver<Job>);
 
-impl Drop for RxWrapper {
+impl Drop for Sentinel {
     fn drop(&mut self) {
         if thread::panicking() {
-            let rx = RxWrapper(Arc::clone(&self.0));
-            thread::spawn(move || loop {
-                let job = rx.0.lock().unwrap().recv();
-                match job {
-                    Ok(f) => {
-                        f();
-                    }
-                    Err(e) => {
-                        debug!("[SharedThreadPool]Maybe Sender was destroyed: {}", e);
-                    }
-                }
-            });
+            run_worker(self.0.clone());
         }
     }
 }